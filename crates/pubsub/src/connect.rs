@@ -0,0 +1,126 @@
+use crate::handle::ConnectionHandle;
+use alloy_transport::TransportResult;
+use std::time::Duration;
+
+/// Configuration for retrying the backend handshake performed by
+/// [`PubSubConnect::try_reconnect`] after a disconnect.
+///
+/// The delay between attempts grows exponentially, starting at
+/// `initial_backoff` and capped at `max_backoff`, so that a provider
+/// recovering from an outage is not hammered with reconnect attempts the
+/// moment it comes back up.
+#[derive(Debug, Clone, Copy)]
+pub struct ReconnectPolicy {
+    /// The delay before the first retry attempt.
+    pub initial_backoff: Duration,
+    /// The maximum delay between retry attempts.
+    pub max_backoff: Duration,
+    /// The factor the delay is multiplied by after each failed attempt.
+    pub multiplier: f64,
+    /// The maximum number of retries to make, after the first attempt,
+    /// before giving up and returning the last error. `None` retries
+    /// forever. `Some(0)` never retries; `Some(1)` retries once (i.e. two
+    /// attempts total) before giving up, and so on.
+    pub max_retries: Option<usize>,
+    /// Whether to randomize each delay by a factor in `[0.5, 1.0]`, to avoid
+    /// a thundering herd of clients reconnecting to the same provider in
+    /// lockstep.
+    pub jitter: bool,
+    /// The maximum number of times a single pending request may be
+    /// re-issued across successive reconnects before it is failed to its
+    /// caller instead of retried again. `None` re-issues forever. This
+    /// bounds the damage a single poison request (one that reliably kills
+    /// the backend every time it is replayed) can do.
+    pub max_reissues: Option<usize>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            max_retries: None,
+            jitter: true,
+            max_reissues: None,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Apply jitter (if configured) to a delay before sleeping on it.
+    pub(crate) fn jittered(&self, delay: Duration) -> Duration {
+        if self.jitter {
+            delay.mul_f64(rand::Rng::gen_range(&mut rand::thread_rng(), 0.5..=1.0))
+        } else {
+            delay
+        }
+    }
+
+    /// Grow `delay` by `multiplier`, capped at `max_backoff`.
+    pub(crate) fn backoff(&self, delay: Duration) -> Duration {
+        delay.mul_f64(self.multiplier).min(self.max_backoff)
+    }
+}
+
+/// Types that can produce (and re-produce) a pubsub backend connection.
+///
+/// Implementors describe how to dial a specific transport (a websocket URL,
+/// an IPC socket path, an in-process channel, ...). [`PubSubService`](crate::service::PubSubService)
+/// calls [`connect`](Self::connect) once at startup and
+/// [`try_reconnect`](Self::try_reconnect) whenever the backend needs to be
+/// re-established.
+#[async_trait::async_trait]
+pub trait PubSubConnect: Sized + Send + Sync + 'static {
+    /// Connect to the backend, returning a handle the service can use to
+    /// send and receive raw JSON-RPC payloads.
+    async fn connect(&self) -> TransportResult<ConnectionHandle>;
+
+    /// Attempt to re-establish the backend connection after it was lost.
+    async fn try_reconnect(&self) -> TransportResult<ConnectionHandle>;
+
+    /// The policy used to retry [`try_reconnect`](Self::try_reconnect) when
+    /// it fails.
+    ///
+    /// Defaults to [`ReconnectPolicy::default()`].
+    fn reconnect_policy(&self) -> ReconnectPolicy {
+        ReconnectPolicy::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_and_caps() {
+        let policy = ReconnectPolicy {
+            multiplier: 2.0,
+            max_backoff: Duration::from_secs(1),
+            ..ReconnectPolicy::default()
+        };
+
+        assert_eq!(policy.backoff(Duration::from_millis(100)), Duration::from_millis(200));
+        assert_eq!(policy.backoff(Duration::from_millis(900)), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let policy = ReconnectPolicy { jitter: true, ..ReconnectPolicy::default() };
+        let delay = Duration::from_millis(100);
+
+        for _ in 0..1000 {
+            let jittered = policy.jittered(delay);
+            assert!(jittered >= delay.mul_f64(0.5));
+            assert!(jittered <= delay);
+        }
+    }
+
+    #[test]
+    fn no_jitter_is_identity() {
+        let policy = ReconnectPolicy { jitter: false, ..ReconnectPolicy::default() };
+        let delay = Duration::from_millis(250);
+
+        assert_eq!(policy.jittered(delay), delay);
+    }
+}
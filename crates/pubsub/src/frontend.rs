@@ -0,0 +1,16 @@
+use crate::ix::PubSubInstruction;
+use tokio::sync::mpsc;
+
+/// A cheaply-cloneable handle to a running [`PubSubService`](crate::service::PubSubService).
+#[derive(Debug, Clone)]
+pub struct PubSubFrontend {
+    instructions: mpsc::UnboundedSender<PubSubInstruction>,
+}
+
+impl PubSubFrontend {
+    /// Create a new frontend from the sending half of the service's
+    /// instruction channel.
+    pub(crate) fn new(instructions: mpsc::UnboundedSender<PubSubInstruction>) -> Self {
+        Self { instructions }
+    }
+}
@@ -0,0 +1,43 @@
+use alloy_json_rpc::PubSubItem;
+use serde_json::value::RawValue;
+use tokio::sync::{mpsc, oneshot};
+
+/// A handle to a running pubsub backend connection.
+///
+/// Owned by [`PubSubService`](crate::service::PubSubService): serialized
+/// outbound requests are pushed into `to_socket`, inbound items are read
+/// from `from_socket`, and `error` resolves if the backend task detects
+/// that the connection died.
+#[derive(Debug)]
+pub(crate) struct ConnectionHandle {
+    /// Outbound channel to the backend.
+    pub(crate) to_socket: mpsc::UnboundedSender<Box<RawValue>>,
+    /// Inbound channel from the backend.
+    pub(crate) from_socket: mpsc::UnboundedReceiver<PubSubItem>,
+    /// Resolves when the backend detects an unrecoverable error.
+    pub(crate) error: oneshot::Receiver<()>,
+    /// Tells the backend task to shut down when this handle is replaced,
+    /// e.g. during a reconnect.
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl ConnectionHandle {
+    /// Assemble a handle from its parts. Called by [`PubSubConnect`](crate::PubSubConnect)
+    /// implementations once they've established the underlying connection
+    /// (a websocket, an IPC socket, an in-process channel, ...).
+    pub(crate) fn new(
+        to_socket: mpsc::UnboundedSender<Box<RawValue>>,
+        from_socket: mpsc::UnboundedReceiver<PubSubItem>,
+        error: oneshot::Receiver<()>,
+        shutdown: oneshot::Sender<()>,
+    ) -> Self {
+        Self { to_socket, from_socket, error, shutdown: Some(shutdown) }
+    }
+
+    /// Tell the backend task behind this handle to shut down.
+    pub(crate) fn shutdown(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
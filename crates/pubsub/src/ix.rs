@@ -0,0 +1,20 @@
+use crate::managers::{InFlight, SubscriptionItem};
+use alloy_primitives::U256;
+use tokio::sync::{broadcast, oneshot};
+
+/// An instruction sent from a [`PubSubFrontend`](crate::PubSubFrontend) (or
+/// one of its clones) to the [`PubSubService`](crate::service::PubSubService)
+/// task that owns the backend connection.
+#[derive(Debug)]
+pub(crate) enum PubSubInstruction {
+    /// Dispatch a single request to the backend.
+    Request(InFlight),
+    /// Dispatch a batch of requests to the backend as a single JSON-RPC
+    /// batch array.
+    Batch(Vec<InFlight>),
+    /// Fetch the broadcast receiver for an existing subscription, by its
+    /// local alias.
+    GetSub(U256, oneshot::Sender<broadcast::Receiver<SubscriptionItem>>),
+    /// Tear down a subscription, by its local alias.
+    Unsubscribe(U256),
+}
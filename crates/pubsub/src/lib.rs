@@ -0,0 +1,24 @@
+//! Ethereum JSON-RPC publish/subscribe transport.
+//!
+//! This crate provides the plumbing shared by pubsub-capable transports
+//! (websocket, IPC, ...): a backend-owning [`PubSubService`](crate::service::PubSubService)
+//! task, a cloneable [`PubSubFrontend`] handle for callers, and the
+//! bookkeeping needed to track in-flight requests and active subscriptions
+//! across reconnects.
+
+#[macro_use]
+extern crate tracing;
+
+mod connect;
+pub use connect::{PubSubConnect, ReconnectPolicy};
+
+mod frontend;
+pub use frontend::PubSubFrontend;
+
+mod handle;
+
+mod ix;
+
+mod managers;
+
+mod service;
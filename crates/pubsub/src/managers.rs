@@ -0,0 +1,373 @@
+use alloy_json_rpc::{EthNotification, Id, Response, ResponsePayload, SerializedRequest};
+use alloy_primitives::{B256, U256};
+use alloy_transport::TransportResult;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::{collections::HashMap, time::Duration};
+use tokio::sync::{broadcast, oneshot};
+
+/// Capacity of a subscription's broadcast channel. Chosen generously so a
+/// slow subscriber falls behind rather than missing items outright; once the
+/// channel is full the oldest buffered item is dropped to make room.
+const SUBSCRIPTION_CHANNEL_SIZE: usize = 8192;
+
+/// The timeout applied to a request that doesn't specify its own, via
+/// [`InFlight::new`].
+pub(crate) const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A request dispatched to the backend, awaiting a response.
+#[derive(Debug)]
+pub(crate) struct InFlight {
+    /// The serialized request, kept around so it can be re-dispatched on
+    /// reconnect.
+    pub(crate) request: SerializedRequest,
+    /// Where to send the response (or error) once it arrives.
+    pub(crate) tx: oneshot::Sender<TransportResult<Response>>,
+    /// How long to wait for a response before failing this request.
+    pub(crate) timeout: Duration,
+    /// How many times this request has been re-dispatched across
+    /// reconnects, so a poison request (one that reliably kills the
+    /// backend every time it's replayed) can eventually be given up on
+    /// instead of retried forever.
+    pub(crate) reissues: usize,
+}
+
+impl InFlight {
+    /// Create a new in-flight request, timing out after
+    /// [`DEFAULT_REQUEST_TIMEOUT`], and the receiver half of its response
+    /// channel.
+    pub(crate) fn new(
+        request: SerializedRequest,
+    ) -> (Self, oneshot::Receiver<TransportResult<Response>>) {
+        Self::with_timeout(request, DEFAULT_REQUEST_TIMEOUT)
+    }
+
+    /// Create a new in-flight request with an explicit timeout, and the
+    /// receiver half of its response channel.
+    pub(crate) fn with_timeout(
+        request: SerializedRequest,
+        timeout: Duration,
+    ) -> (Self, oneshot::Receiver<TransportResult<Response>>) {
+        let (tx, rx) = oneshot::channel();
+        (Self { request, tx, timeout, reissues: 0 }, rx)
+    }
+
+    /// The serialized request.
+    pub(crate) fn request(&self) -> &SerializedRequest {
+        &self.request
+    }
+
+    /// How long to wait for a response before failing this request.
+    pub(crate) fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Record that this request is being re-dispatched after a reconnect.
+    pub(crate) fn mark_reissued(&mut self) {
+        self.reissues += 1;
+    }
+}
+
+/// Tracks requests that have been dispatched to the backend and are
+/// awaiting a response, keyed by request [`Id`].
+#[derive(Debug, Default)]
+pub(crate) struct RequestManager {
+    reqs: HashMap<Id, InFlight>,
+}
+
+impl RequestManager {
+    /// The number of requests currently in flight.
+    pub(crate) fn len(&self) -> usize {
+        self.reqs.len()
+    }
+
+    /// Track a dispatched request, keyed by its own id.
+    pub(crate) fn insert(&mut self, in_flight: InFlight) {
+        self.reqs.insert(in_flight.request.id().clone(), in_flight);
+    }
+
+    /// Stop tracking a request, e.g. because it timed out.
+    pub(crate) fn remove(&mut self, id: &Id) -> Option<InFlight> {
+        self.reqs.remove(id)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&Id, &InFlight)> {
+        self.reqs.iter()
+    }
+
+    /// Take every in-flight request, leaving this manager empty. Used when
+    /// re-issuing requests across a reconnect.
+    pub(crate) fn drain(&mut self) -> impl Iterator<Item = InFlight> + '_ {
+        self.reqs.drain().map(|(_, in_flight)| in_flight)
+    }
+
+    /// Match an incoming response to its in-flight request by id.
+    ///
+    /// Ordinary requests are delivered to their waiter directly, and `None`
+    /// is returned. A successful `eth_subscribe` response is special: its
+    /// payload is the server-assigned subscription id, which the caller
+    /// must rewrite to a local alias before it is delivered, so it is
+    /// returned alongside the completed [`InFlight`] instead.
+    pub(crate) fn handle_response(&mut self, resp: Response) -> Option<(U256, InFlight)> {
+        let in_flight = self.reqs.remove(&resp.id)?;
+
+        if in_flight.request.method() != "eth_subscribe" {
+            let _ = in_flight.tx.send(Ok(resp));
+            return None;
+        }
+
+        match &resp.payload {
+            ResponsePayload::Success(payload) => match serde_json::from_str::<U256>(payload.get())
+            {
+                Ok(server_id) => Some((server_id, in_flight)),
+                Err(_) => {
+                    let _ = in_flight.tx.send(Ok(resp));
+                    None
+                }
+            },
+            ResponsePayload::Failure(_) => {
+                let _ = in_flight.tx.send(Ok(resp));
+                None
+            }
+        }
+    }
+}
+
+/// Why a subscription's stream ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CloseReason {
+    /// The caller explicitly unsubscribed.
+    Unsubscribed,
+    /// The server sent a notification indicating it closed the subscription
+    /// on its end (see [`SubscriptionManager::notify`]).
+    ServerClosed,
+    /// The local transport was shut down, e.g. because the owning
+    /// [`PubSubFrontend`](crate::PubSubFrontend) was dropped.
+    TransportClosed,
+    /// The backend connection was lost and could not be re-established.
+    BackendGone,
+}
+
+/// An item delivered to a subscriber of an `eth_subscribe` subscription.
+#[derive(Debug, Clone)]
+pub(crate) enum SubscriptionItem {
+    /// A single notification payload.
+    Item(Box<RawValue>),
+    /// The subscription's stream has ended and no further items will be
+    /// delivered.
+    Closed(CloseReason),
+}
+
+/// An `eth_subscribe` subscription that is currently active.
+#[derive(Debug)]
+pub(crate) struct ActiveSubscription {
+    /// The `eth_subscribe` request that created this subscription, kept
+    /// around so it can be re-dispatched on reconnect.
+    request: SerializedRequest,
+    /// The server-assigned subscription id, if the server has responded to
+    /// the (re-)subscribe request yet. `None` immediately after a
+    /// reconnect, until the re-issued `eth_subscribe` completes.
+    server_id: Option<U256>,
+    /// Delivers notifications to subscribers.
+    tx: broadcast::Sender<SubscriptionItem>,
+}
+
+impl ActiveSubscription {
+    /// The `eth_subscribe` request that created this subscription.
+    pub(crate) fn request(&self) -> &SerializedRequest {
+        &self.request
+    }
+}
+
+/// Tracks active subscriptions, keyed by the local alias handed out to
+/// callers, and maps server-assigned subscription ids back to that alias.
+///
+/// Local aliases are used (instead of the server's own subscription ids)
+/// because the server assigns a fresh id every time a subscription is
+/// re-established across a reconnect, but callers need a stable id to keep
+/// receiving the same subscription's notifications with.
+#[derive(Debug, Default)]
+pub(crate) struct SubscriptionManager {
+    subs: HashMap<B256, ActiveSubscription>,
+    server_to_local: HashMap<U256, B256>,
+}
+
+impl SubscriptionManager {
+    /// The number of currently active subscriptions.
+    pub(crate) fn len(&self) -> usize {
+        self.subs.len()
+    }
+
+    /// Forget every server-assigned id, e.g. before re-subscribing after a
+    /// reconnect. Incoming notifications have nowhere to be routed until
+    /// the re-issued `eth_subscribe` requests complete and [`Self::upsert`]
+    /// records their new ids.
+    pub(crate) fn drop_server_ids(&mut self) {
+        self.server_to_local.clear();
+        for sub in self.subs.values_mut() {
+            sub.server_id = None;
+        }
+    }
+
+    /// Iterate over active subscriptions, keyed by local alias.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&B256, &ActiveSubscription)> {
+        self.subs.iter()
+    }
+
+    /// Subscribe to the broadcast channel for a local alias, if it exists.
+    pub(crate) fn get_rx(&self, local_id: B256) -> Option<broadcast::Receiver<SubscriptionItem>> {
+        self.subs.get(&local_id).map(|sub| sub.tx.subscribe())
+    }
+
+    /// Stop tracking a subscription and tell its subscribers why, e.g.
+    /// because the caller unsubscribed or the server closed it.
+    pub(crate) fn close(&mut self, local_id: B256, reason: CloseReason) {
+        if let Some(sub) = self.subs.remove(&local_id) {
+            if let Some(server_id) = sub.server_id {
+                self.server_to_local.remove(&server_id);
+            }
+            let _ = sub.tx.send(SubscriptionItem::Closed(reason));
+        }
+    }
+
+    /// Stop tracking every subscription and tell their subscribers why, e.g.
+    /// because the backend connection was lost for good.
+    pub(crate) fn close_all(&mut self, reason: CloseReason) {
+        self.server_to_local.clear();
+        for (_, sub) in self.subs.drain() {
+            let _ = sub.tx.send(SubscriptionItem::Closed(reason));
+        }
+    }
+
+    /// Deliver a notification from the backend to the local alias it was
+    /// assigned to, if any subscriber is still tracking it.
+    ///
+    /// Some providers signal that they are ending a subscription on their
+    /// end by pushing a final notification through the subscription's own
+    /// channel whose `result` is an error envelope (`{"error": {...}}`)
+    /// instead of an ordinary result payload — used for things like
+    /// rate-limit cutoffs or an upstream filter expiring. Ordinary
+    /// subscription results (block headers, logs, pending tx hashes, ...)
+    /// never take this shape, so detecting it is unambiguous; an explicit
+    /// close this way is treated as [`CloseReason::ServerClosed`] rather
+    /// than forwarded as an item.
+    pub(crate) fn notify(&mut self, notification: EthNotification<Box<RawValue>>) {
+        let Some(&local_id) = self.server_to_local.get(&notification.subscription) else { return };
+
+        if serde_json::from_str::<ProviderCloseNotice>(notification.result.get()).is_ok() {
+            self.close(local_id, CloseReason::ServerClosed);
+            return;
+        }
+
+        if let Some(sub) = self.subs.get(&local_id) {
+            let _ = sub.tx.send(SubscriptionItem::Item(notification.result));
+        }
+    }
+
+    /// Record (or re-record, after a reconnect) the server-assigned id for
+    /// the subscription created by `request`, creating its broadcast
+    /// channel the first time the subscription is seen.
+    ///
+    /// The local alias is derived from `request`'s own id rather than from
+    /// `server_id`, so that re-subscribing the same request after a
+    /// reconnect (which keeps the request's id but gets a new server id)
+    /// resolves back to the same alias instead of minting a new one.
+    pub(crate) fn upsert(&mut self, request: SerializedRequest, server_id: U256) {
+        let local_id = local_alias(&request);
+        self.server_to_local.insert(server_id, local_id);
+        self.subs
+            .entry(local_id)
+            .and_modify(|sub| sub.server_id = Some(server_id))
+            .or_insert_with(|| ActiveSubscription {
+                request,
+                server_id: Some(server_id),
+                tx: broadcast::channel(SUBSCRIPTION_CHANNEL_SIZE).0,
+            });
+    }
+
+    /// The local alias for a server-assigned subscription id, if tracked.
+    pub(crate) fn local_id_for(&self, server_id: U256) -> Option<B256> {
+        self.server_to_local.get(&server_id).copied()
+    }
+}
+
+/// Derive a subscription's local alias from the id of the `eth_subscribe`
+/// request that created it. Request ids are always [`Id::Number`] for
+/// requests we generate ourselves.
+fn local_alias(request: &SerializedRequest) -> B256 {
+    let n = match request.id() {
+        Id::Number(n) => *n,
+        Id::String(_) | Id::None => unreachable!("pubsub requests are always assigned numeric ids"),
+    };
+    B256::from(U256::from(n).to_be_bytes())
+}
+
+/// The shape of the error envelope a provider pushes through a
+/// subscription's channel to signal that it is closing the subscription.
+/// Only matches objects carrying an `error` field, which no ordinary
+/// subscription result does.
+#[derive(Deserialize)]
+struct ProviderCloseNotice {
+    #[allow(dead_code)]
+    error: ProviderCloseError,
+}
+
+#[derive(Deserialize)]
+struct ProviderCloseError {
+    #[allow(dead_code)]
+    message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_json_rpc::{Request, RequestMeta};
+
+    fn test_sub(n: u64) -> (SerializedRequest, B256) {
+        let req = Request {
+            meta: RequestMeta { id: Id::Number(n), method: "eth_subscribe" },
+            params: (),
+        };
+        let serialized = req.serialize().expect("request always serializes");
+        let alias = local_alias(&serialized);
+        (serialized, alias)
+    }
+
+    #[test]
+    fn ordinary_notification_forwards_as_item() {
+        let mut subs = SubscriptionManager::default();
+        let (request, local_id) = test_sub(1);
+        let server_id = U256::from(7);
+        subs.upsert(request, server_id);
+        let mut rx = subs.get_rx(local_id).unwrap();
+
+        let result = RawValue::from_string(r#"{"number":"0x1"}"#.to_owned()).unwrap();
+        subs.notify(EthNotification { subscription: server_id, result });
+
+        match rx.try_recv().unwrap() {
+            SubscriptionItem::Item(_) => {}
+            SubscriptionItem::Closed(reason) => panic!("expected Item, got Closed({reason:?})"),
+        }
+        assert_eq!(subs.len(), 1);
+    }
+
+    #[test]
+    fn server_close_envelope_ends_the_subscription() {
+        let mut subs = SubscriptionManager::default();
+        let (request, local_id) = test_sub(1);
+        let server_id = U256::from(7);
+        subs.upsert(request, server_id);
+        let mut rx = subs.get_rx(local_id).unwrap();
+
+        let result =
+            RawValue::from_string(r#"{"error":{"message":"subscription limit exceeded"}}"#.to_owned())
+                .unwrap();
+        subs.notify(EthNotification { subscription: server_id, result });
+
+        match rx.try_recv().unwrap() {
+            SubscriptionItem::Closed(CloseReason::ServerClosed) => {}
+            other => panic!("expected Closed(ServerClosed), got {other:?}"),
+        }
+        assert_eq!(subs.len(), 0);
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{
+    connect::ReconnectPolicy,
     handle::ConnectionHandle,
     ix::PubSubInstruction,
-    managers::{InFlight, RequestManager, SubscriptionManager},
+    managers::{CloseReason, InFlight, RequestManager, SubscriptionItem, SubscriptionManager},
     PubSubConnect, PubSubFrontend,
 };
 
@@ -12,7 +13,9 @@ use alloy_transport::{
     TransportError, TransportErrorKind, TransportResult,
 };
 use serde_json::value::RawValue;
+use std::{collections::BTreeMap, time::Instant};
 use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio::time::Instant as TokioInstant;
 
 #[derive(Debug)]
 /// The service contains the backend handle, a subscription manager, and the
@@ -24,6 +27,9 @@ pub(crate) struct PubSubService<T> {
     /// The configuration details required to reconnect.
     pub(crate) connector: T,
 
+    /// The policy used to retry the backend handshake on reconnect.
+    pub(crate) policy: ReconnectPolicy,
+
     /// The inbound requests.
     pub(crate) reqs: mpsc::UnboundedReceiver<PubSubInstruction>,
 
@@ -32,6 +38,11 @@ pub(crate) struct PubSubService<T> {
 
     /// The request manager.
     pub(crate) in_flights: RequestManager,
+
+    /// Pending request deadlines, in the order they'll expire. Several
+    /// requests can share a deadline (e.g. if they were dispatched in the
+    /// same batch), so each entry holds every [`Id`] due at that instant.
+    pub(crate) deadlines: BTreeMap<Instant, Vec<Id>>,
 }
 
 impl<T> PubSubService<T>
@@ -41,26 +52,53 @@ where
     /// Create a new service from a connector.
     pub(crate) async fn connect(connector: T) -> Result<PubSubFrontend, TransportError> {
         let handle = connector.connect().await?;
+        let policy = connector.reconnect_policy();
 
         let (tx, reqs) = mpsc::unbounded_channel();
         let this = Self {
             handle,
             connector,
+            policy,
             reqs,
             subs: Default::default(),
             in_flights: Default::default(),
+            deadlines: Default::default(),
         };
         this.spawn();
         Ok(PubSubFrontend::new(tx))
     }
 
-    /// Reconnect by dropping the backend and creating a new one.
+    /// Reconnect by dropping the backend and creating a new one, retrying
+    /// according to [`Self::policy`] if the first attempt fails.
     async fn get_new_backend(&mut self) -> Result<ConnectionHandle, TransportError> {
-        let mut handle = self.connector.try_reconnect().await?;
+        let mut handle = self.get_new_backend_with_retries().await?;
         std::mem::swap(&mut self.handle, &mut handle);
         Ok(handle)
     }
 
+    /// Retry [`PubSubConnect::try_reconnect`] with exponential backoff until
+    /// it succeeds or the policy's `max_retries` is exhausted.
+    async fn get_new_backend_with_retries(&self) -> Result<ConnectionHandle, TransportError> {
+        let mut delay = self.policy.initial_backoff;
+        let mut attempt = 0usize;
+
+        loop {
+            match self.connector.try_reconnect().await {
+                Ok(handle) => return Ok(handle),
+                Err(err) => {
+                    if self.policy.max_retries.is_some_and(|max| attempt >= max) {
+                        return Err(err);
+                    }
+
+                    warn!(%err, attempt, "pubsub reconnect attempt failed, retrying");
+                    tokio::time::sleep(self.policy.jittered(delay)).await;
+                    delay = self.policy.backoff(delay);
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
     /// Reconnect the backend, re-issue pending requests, and re-start active
     /// subscriptions.
     async fn reconnect(&mut self) -> TransportResult<()> {
@@ -77,14 +115,7 @@ where
 
         old_handle.shutdown();
 
-        // Re-issue pending requests.
-        debug!(count = self.in_flights.len(), "Reissuing pending requests");
-        self.in_flights
-            .iter()
-            .map(|(_, in_flight)| in_flight.request().serialized().to_owned())
-            .collect::<Vec<_>>()
-            .into_iter()
-            .try_for_each(|brv| self.dispatch_request(brv))?;
+        self.reissue_pending()?;
 
         // Re-subscribe to all active subscriptions
         debug!(count = self.subs.len(), "Re-starting active subscriptions");
@@ -108,6 +139,53 @@ where
         Ok(())
     }
 
+    /// Re-dispatch every pending request after a reconnect, resetting their
+    /// deadlines. All requests re-issued in this pass share a single `now`,
+    /// so those with equal timeouts land on the same deadline bucket instead
+    /// of drifting apart by however long re-dispatching each one takes.
+    ///
+    /// A request that has already been re-issued [`ReconnectPolicy::max_reissues`]
+    /// times is failed to its caller instead of re-dispatched again, so a
+    /// single poison request (one that reliably kills the backend every
+    /// time it's replayed) can't loop forever.
+    fn reissue_pending(&mut self) -> TransportResult<()> {
+        debug!(count = self.in_flights.len(), "Reissuing pending requests");
+        self.deadlines.clear();
+        let now = Instant::now();
+
+        for mut in_flight in self.in_flights.drain().collect::<Vec<_>>() {
+            if self.policy.max_reissues.is_some_and(|max| in_flight.reissues >= max) {
+                warn!(reissues = in_flight.reissues, "giving up on request after too many reissues");
+                let _ = in_flight.tx.send(Err(TransportErrorKind::custom_str(
+                    "request exceeded the maximum number of reissues across reconnects",
+                )));
+                continue;
+            }
+
+            in_flight.mark_reissued();
+            let brv = in_flight.request().serialized().to_owned();
+            let id = in_flight.request().id().clone();
+            let deadline = now + in_flight.timeout();
+
+            self.dispatch_request(brv)?;
+            self.in_flights.insert(in_flight);
+            self.schedule_deadline(id, deadline);
+        }
+
+        Ok(())
+    }
+
+    /// Fail every currently in-flight request, e.g. because the backend
+    /// connection was lost and could not be re-established.
+    fn fail_all_in_flights(&mut self, err: &TransportError) {
+        for in_flight in self.in_flights.drain().collect::<Vec<_>>() {
+            let _ = in_flight
+                .tx
+                .send(Err(TransportErrorKind::custom_str(&format!("backend gone: {err}"))));
+        }
+        self.deadlines.clear();
+    }
+
     /// Dispatch a request to the socket.
     fn dispatch_request(&mut self, brv: Box<RawValue>) -> TransportResult<()> {
         self.handle.to_socket.send(brv).map(drop).map_err(|_| TransportErrorKind::backend_gone())
@@ -116,13 +194,79 @@ where
     /// Service a request.
     fn service_request(&mut self, in_flight: InFlight) -> TransportResult<()> {
         let brv = in_flight.request();
-
         self.dispatch_request(brv.serialized().to_owned())?;
+
+        let id = in_flight.request().id().clone();
+        let deadline = Instant::now() + in_flight.timeout();
         self.in_flights.insert(in_flight);
+        self.schedule_deadline(id, deadline);
 
         Ok(())
     }
 
+    /// Service a batch of requests, dispatching them as a single JSON-RPC
+    /// batch array so the backend can answer them together.
+    ///
+    /// All requests in the batch share one computed `now`, so those with
+    /// equal timeouts land on the same deadline bucket instead of drifting
+    /// apart by however long serializing and dispatching the batch takes.
+    fn service_batch(&mut self, in_flights: Vec<InFlight>) -> TransportResult<()> {
+        let mut buf = String::from("[");
+        for (i, in_flight) in in_flights.iter().enumerate() {
+            if i > 0 {
+                buf.push(',');
+            }
+            buf.push_str(in_flight.request().serialized().get());
+        }
+        buf.push(']');
+        let batch = RawValue::from_string(buf).expect("batch of valid requests is valid json");
+
+        self.dispatch_request(batch)?;
+
+        let now = Instant::now();
+        for in_flight in in_flights {
+            let id = in_flight.request().id().clone();
+            let deadline = now + in_flight.timeout();
+            self.in_flights.insert(in_flight);
+            self.schedule_deadline(id, deadline);
+        }
+
+        Ok(())
+    }
+
+    /// Track that `id` should time out at `deadline`, unless a response (or
+    /// another timeout) arrives first.
+    fn schedule_deadline(&mut self, id: Id, deadline: Instant) {
+        self.deadlines.entry(deadline).or_default().push(id);
+    }
+
+    /// Fail every in-flight request whose deadline has passed, removing them
+    /// from both [`Self::in_flights`] and [`Self::deadlines`].
+    fn handle_timeout(&mut self) {
+        // `split_off` keeps keys `< split_point` in `self.deadlines` and
+        // moves keys `>= split_point` into the returned map, so splitting
+        // one nanosecond past `now` keeps an exactly-`now` deadline on the
+        // "expired" side.
+        let now = Instant::now();
+        let not_yet_due = self.deadlines.split_off(&(now + std::time::Duration::from_nanos(1)));
+        let expired = std::mem::replace(&mut self.deadlines, not_yet_due);
+
+        for (_, ids) in expired {
+            for id in ids {
+                if let Some(in_flight) = self.in_flights.remove(&id) {
+                    let _ = in_flight.tx.send(Err(TransportErrorKind::custom_str(
+                        "request timed out waiting for a response",
+                    )));
+                }
+            }
+        }
+    }
+
+    /// The instant at which the next request deadline expires, if any.
+    fn next_deadline(&self) -> Option<Instant> {
+        self.deadlines.keys().next().copied()
+    }
+
     /// Service a GetSub instruction.
     ///
     /// If the subscription exists, the waiter is sent a broadcast receiver. If
@@ -132,7 +276,7 @@ where
     fn service_get_sub(
         &mut self,
         local_id: U256,
-        tx: oneshot::Sender<broadcast::Receiver<Box<RawValue>>>,
+        tx: oneshot::Sender<broadcast::Receiver<SubscriptionItem>>,
     ) -> TransportResult<()> {
         let local_id = local_id.into();
 
@@ -153,7 +297,7 @@ where
         let brv = req.serialize().expect("no ser error").take_request();
 
         self.dispatch_request(brv)?;
-        self.subs.remove_sub(local_id);
+        self.subs.close(local_id, CloseReason::Unsubscribed);
         Ok(())
     }
 
@@ -162,6 +306,7 @@ where
         trace!(?ix, "servicing instruction");
         match ix {
             PubSubInstruction::Request(in_flight) => self.service_request(in_flight),
+            PubSubInstruction::Batch(in_flights) => self.service_batch(in_flights),
             PubSubInstruction::GetSub(alias, tx) => self.service_get_sub(alias, tx),
             PubSubInstruction::Unsubscribe(alias) => self.service_unsubscribe(alias),
         }
@@ -170,10 +315,10 @@ where
     /// Handle an item from the backend.
     fn handle_item(&mut self, item: PubSubItem) -> TransportResult<()> {
         match item {
-            PubSubItem::Response(resp) => match self.in_flights.handle_response(resp) {
-                Some((server_id, in_flight)) => self.handle_sub_response(in_flight, server_id),
-                None => Ok(()),
-            },
+            PubSubItem::Response(resp) => self.handle_response(resp),
+            PubSubItem::BatchResponse(resps) => {
+                resps.into_iter().try_for_each(|r| self.handle_response(r))
+            }
             PubSubItem::Notification(notification) => {
                 self.subs.notify(notification);
                 Ok(())
@@ -181,6 +326,15 @@ where
         }
     }
 
+    /// Handle a single response, whether it arrived on its own or as part of
+    /// a batch.
+    fn handle_response(&mut self, resp: Response) -> TransportResult<()> {
+        match self.in_flights.handle_response(resp) {
+            Some((server_id, in_flight)) => self.handle_sub_response(in_flight, server_id),
+            None => Ok(()),
+        }
+    }
+
     /// Rewrite the subscription id and insert into the subscriptions manager
     fn handle_sub_response(&mut self, in_flight: InFlight, server_id: U256) -> TransportResult<()> {
         let request = in_flight.request;
@@ -204,7 +358,20 @@ where
     /// Spawn the service.
     pub(crate) fn spawn(mut self) {
         let fut = async move {
+            // Fires when the next request deadline expires. Reset on every
+            // iteration of the loop to track `self.deadlines`, which changes
+            // as requests are dispatched, answered, and timed out.
+            let sleep = tokio::time::sleep(std::time::Duration::from_secs(3600));
+            tokio::pin!(sleep);
+
             let result: TransportResult<()> = loop {
+                match self.next_deadline() {
+                    Some(deadline) => sleep.as_mut().reset(TokioInstant::from_std(deadline)),
+                    None => {
+                        sleep.as_mut().reset(TokioInstant::now() + std::time::Duration::from_secs(3600))
+                    }
+                }
+
                 // We bias the loop so that we always handle new messages before
                 // reconnecting, and always reconnect before dispatching new
                 // requests.
@@ -235,16 +402,150 @@ where
                             }
                         } else {
                             info!("Pubsub service request channel closed. Shutting down.");
+                            self.subs.close_all(CloseReason::TransportClosed);
                            break Ok(())
                         }
                     }
+
+                    _ = &mut sleep => {
+                        self.handle_timeout();
+                    }
                 }
             };
 
             if let Err(err) = result {
                 error!(%err, "pubsub service reconnection error");
+                self.fail_all_in_flights(&err);
+                self.subs.close_all(CloseReason::BackendGone);
             }
         };
         fut.spawn_task();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handle::ConnectionHandle;
+    use alloy_json_rpc::{Request, RequestMeta};
+
+    /// A [`PubSubConnect`] that never successfully (re)connects. Fine for
+    /// tests that drive a [`PubSubService`] directly and never hit the
+    /// reconnect path.
+    struct NoopConnect;
+
+    #[async_trait::async_trait]
+    impl PubSubConnect for NoopConnect {
+        async fn connect(&self) -> TransportResult<ConnectionHandle> {
+            Err(TransportErrorKind::custom_str("NoopConnect cannot connect"))
+        }
+
+        async fn try_reconnect(&self) -> TransportResult<ConnectionHandle> {
+            Err(TransportErrorKind::custom_str("NoopConnect cannot reconnect"))
+        }
+    }
+
+    /// Build a [`ConnectionHandle`] backed by in-memory channels, with
+    /// nothing driving its other end, plus the receiving half of its
+    /// outbound channel so a test can inspect what the service dispatches.
+    fn test_handle() -> (ConnectionHandle, mpsc::UnboundedReceiver<Box<RawValue>>) {
+        let (to_socket, to_socket_rx) = mpsc::unbounded_channel();
+        let (_from_socket_tx, from_socket) = mpsc::unbounded_channel();
+        let (_error_tx, error) = oneshot::channel();
+        let (shutdown, _shutdown_rx) = oneshot::channel();
+        (ConnectionHandle::new(to_socket, from_socket, error, shutdown), to_socket_rx)
+    }
+
+    fn test_service() -> (PubSubService<NoopConnect>, mpsc::UnboundedReceiver<Box<RawValue>>) {
+        let (handle, to_socket_rx) = test_handle();
+        let (_tx, reqs) = mpsc::unbounded_channel();
+        let service = PubSubService {
+            handle,
+            connector: NoopConnect,
+            policy: ReconnectPolicy::default(),
+            reqs,
+            subs: Default::default(),
+            in_flights: Default::default(),
+            deadlines: Default::default(),
+        };
+        (service, to_socket_rx)
+    }
+
+    fn test_in_flight(n: u64) -> (InFlight, oneshot::Receiver<TransportResult<Response>>) {
+        let req = Request { meta: RequestMeta { id: Id::Number(n), method: "eth_test" }, params: () };
+        let serialized = req.serialize().expect("request always serializes");
+        InFlight::new(serialized)
+    }
+
+    #[tokio::test]
+    async fn same_instant_deadline_fires_all_ids() {
+        let (mut service, _to_socket_rx) = test_service();
+
+        let (in_flight_a, rx_a) = test_in_flight(1);
+        let (in_flight_b, rx_b) = test_in_flight(2);
+        let id_a = in_flight_a.request().id().clone();
+        let id_b = in_flight_b.request().id().clone();
+        service.in_flights.insert(in_flight_a);
+        service.in_flights.insert(in_flight_b);
+
+        // Both requests share the exact same deadline, as if they'd been
+        // dispatched in the same batch or re-issued in the same reconnect
+        // pass.
+        let deadline = Instant::now();
+        service.schedule_deadline(id_a, deadline);
+        service.schedule_deadline(id_b, deadline);
+
+        service.handle_timeout();
+
+        assert!(service.in_flights.iter().next().is_none());
+        assert!(rx_a.await.expect("closed without a response").is_err());
+        assert!(rx_b.await.expect("closed without a response").is_err());
+    }
+
+    #[tokio::test]
+    async fn batch_dispatches_as_one_array_and_splits_responses() {
+        let (mut service, mut to_socket_rx) = test_service();
+
+        let (in_flight_a, rx_a) = test_in_flight(1);
+        let (in_flight_b, rx_b) = test_in_flight(2);
+        let id_a = in_flight_a.request().id().clone();
+        let id_b = in_flight_b.request().id().clone();
+
+        service.service_batch(vec![in_flight_a, in_flight_b]).expect("batch dispatches");
+
+        // The batch goes out as a single message, a JSON array of both
+        // requests, not two separate messages.
+        let sent = to_socket_rx.try_recv().expect("batch was dispatched");
+        assert!(to_socket_rx.try_recv().is_err());
+        let parsed: serde_json::Value = serde_json::from_str(sent.get()).unwrap();
+        assert_eq!(parsed.as_array().expect("batch is a json array").len(), 2);
+
+        // The backend answers with a single BatchResponse; each response
+        // must be routed back to its own caller.
+        let resp_a = Response { id: id_a, payload: ResponsePayload::Success(sent.clone()) };
+        let resp_b = Response { id: id_b, payload: ResponsePayload::Success(sent) };
+        service.handle_item(PubSubItem::BatchResponse(vec![resp_a, resp_b])).unwrap();
+
+        assert!(rx_a.await.expect("closed without a response").is_ok());
+        assert!(rx_b.await.expect("closed without a response").is_ok());
+        assert_eq!(service.in_flights.iter().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn reissue_past_max_reissues_fails_the_caller() {
+        let (mut service, mut to_socket_rx) = test_service();
+        service.policy.max_reissues = Some(1);
+
+        let (mut in_flight, rx) = test_in_flight(1);
+        // Already reissued once, at the cap, so this reconnect must fail it
+        // instead of dispatching it a third time.
+        in_flight.reissues = 1;
+        service.in_flights.insert(in_flight);
+
+        service.reissue_pending().unwrap();
+
+        assert!(to_socket_rx.try_recv().is_err());
+        assert!(rx.await.expect("closed without a response").is_err());
+        assert_eq!(service.in_flights.iter().count(), 0);
+    }
+}